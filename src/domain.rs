@@ -0,0 +1,138 @@
+use serde_json::{json, Value};
+
+use mcp_rust_sdk::error::Error;
+
+use crate::SqlHandler;
+
+/// Dojo/Torii's indexer layout: entities live in `entities`, are linked to
+/// the models they carry via `entity_model`, and model definitions live in
+/// `models`. Kept in one place so a renamed/missing table only needs a
+/// schema check updated here rather than in every tool.
+const ENTITIES_TABLE: &str = "entities";
+const ENTITY_MODEL_TABLE: &str = "entity_model";
+const MODELS_TABLE: &str = "models";
+const TOKENS_TABLE: &str = "tokens";
+
+/// Bail out with a helpful message instead of a confusing SQL error when a
+/// Torii version has renamed or dropped a table a domain tool depends on.
+async fn require_tables(handler: &SqlHandler, tables: &[&str]) -> Result<(), Error> {
+    let schema = handler.fetch_schema(None).await?;
+    for table in tables {
+        if !schema.contains_key(*table) {
+            return Err(Error::Other(format!(
+                "This Torii instance has no '{}' table; use the schema tool to see what's available",
+                table
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `entities` tool: list entities, optionally filtered by the model they
+/// carry and by key, paginated.
+pub async fn entities(
+    handler: &SqlHandler,
+    model: Option<&str>,
+    key: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Value, Error> {
+    require_tables(handler, &[ENTITIES_TABLE, ENTITY_MODEL_TABLE, MODELS_TABLE]).await?;
+
+    let query = "SELECT e.id, e.keys, e.event_id, e.created_at, e.updated_at
+         FROM entities e
+         JOIN entity_model em ON em.entity_id = e.id
+         JOIN models m ON m.id = em.model_id
+         WHERE ($model IS NULL OR m.name = $model)
+           AND ($key IS NULL OR e.keys LIKE $key)
+         ORDER BY e.event_id DESC
+         LIMIT $limit OFFSET $offset";
+
+    let mut params = serde_json::Map::new();
+    params.insert("model".to_string(), json!(model));
+    params.insert("key".to_string(), json!(key.map(|k| format!("%{}%", k))));
+    params.insert("limit".to_string(), json!(limit));
+    params.insert("offset".to_string(), json!(offset));
+
+    let rows = handler.run_query(query, Some(&params)).await?;
+    Ok(json!({ "entities": rows }))
+}
+
+/// `models` tool: list registered Dojo models and their member schemas.
+pub async fn models(handler: &SqlHandler) -> Result<Value, Error> {
+    require_tables(handler, &[MODELS_TABLE]).await?;
+
+    let rows = handler
+        .run_query(
+            "SELECT id, name, namespace, class_hash, packed_size, unpacked_size
+             FROM models
+             ORDER BY name",
+            None,
+        )
+        .await?;
+
+    let schema = handler.fetch_schema(None).await?;
+
+    let models_with_members: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            let model_name = row.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            let namespace = row.get("namespace").and_then(|n| n.as_str()).unwrap_or_default();
+            let members = resolve_model_table(&schema, namespace, model_name)
+                .cloned()
+                .unwrap_or_else(|| json!({ "columns": {} }));
+            json!({ "model": row, "members": members })
+        })
+        .collect();
+
+    Ok(json!({ "models": models_with_members }))
+}
+
+/// Look up a model's per-instance table in `schema`. Torii names these
+/// `{namespace}-{model_name}` rather than the bare model name, so a direct
+/// `schema.get(model_name)` silently misses; try that convention first, then
+/// fall back to a case-insensitive match on the bare model name or on a
+/// `-{model_name}` suffix, so a Torii version that cases things differently
+/// or uses another namespace still resolves. Other separators than `-` are
+/// not matched.
+fn resolve_model_table<'a>(
+    schema: &'a serde_json::Map<String, Value>,
+    namespace: &str,
+    model_name: &str,
+) -> Option<&'a Value> {
+    if let Some(table) = schema.get(&format!("{}-{}", namespace, model_name)) {
+        return Some(table);
+    }
+
+    let suffix = format!("-{}", model_name.to_lowercase());
+    schema
+        .iter()
+        .find(|(table_name, _)| {
+            table_name.eq_ignore_ascii_case(model_name) || table_name.to_lowercase().ends_with(&suffix)
+        })
+        .map(|(_, table)| table)
+}
+
+/// `tokens` tool: query the indexed token table, optionally filtered by
+/// contract address, paginated.
+pub async fn tokens(
+    handler: &SqlHandler,
+    contract_address: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Value, Error> {
+    require_tables(handler, &[TOKENS_TABLE]).await?;
+
+    let query = "SELECT * FROM tokens
+         WHERE ($contract_address IS NULL OR contract_address = $contract_address)
+         ORDER BY id
+         LIMIT $limit OFFSET $offset";
+
+    let mut params = serde_json::Map::new();
+    params.insert("contract_address".to_string(), json!(contract_address));
+    params.insert("limit".to_string(), json!(limit));
+    params.insert("offset".to_string(), json!(offset));
+
+    let rows = handler.run_query(query, Some(&params)).await?;
+    Ok(json!({ "tokens": rows }))
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use mcp_rust_sdk::error::Error;
+
+/// Shape of the result returned by the `query` tool. Defaults to `Raw` so
+/// existing callers are unaffected.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Whatever the remote endpoint returned, untouched.
+    Raw,
+    /// One `{column: value}` object per row.
+    Objects,
+    /// A single `{column: [values...]}` layout, cheaper for a model to scan.
+    Columns,
+}
+
+impl OutputFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self, Error> {
+        match value.unwrap_or("raw") {
+            "raw" => Ok(OutputFormat::Raw),
+            "objects" => Ok(OutputFormat::Objects),
+            "columns" => Ok(OutputFormat::Columns),
+            other => Err(Error::Other(format!(
+                "Unknown format '{}', expected raw, objects, or columns",
+                other
+            ))),
+        }
+    }
+}
+
+/// Reshape `rows` (as returned by the remote SQL endpoint) per `format`,
+/// coercing each value to the SQLite affinity of its column where one is
+/// known in `column_types` (column name -> declared SQLite type, as reported
+/// by `schema`). Columns with no known type, or values that already match,
+/// are passed through unchanged.
+pub fn reshape(rows: Vec<Value>, format: OutputFormat, column_types: &HashMap<String, String>) -> Value {
+    match format {
+        OutputFormat::Raw => Value::Array(rows),
+        OutputFormat::Objects => Value::Array(
+            rows.into_iter()
+                .map(|row| coerce_row(row, column_types))
+                .collect(),
+        ),
+        OutputFormat::Columns => {
+            let mut columns: Map<String, Value> = Map::new();
+            for row in rows {
+                let row = coerce_row(row, column_types);
+                if let Value::Object(row) = row {
+                    for (column, value) in row {
+                        columns
+                            .entry(column)
+                            .or_insert_with(|| json!([]))
+                            .as_array_mut()
+                            .unwrap()
+                            .push(value);
+                    }
+                }
+            }
+            Value::Object(columns)
+        }
+    }
+}
+
+fn coerce_row(row: Value, column_types: &HashMap<String, String>) -> Value {
+    let Value::Object(row) = row else {
+        return row;
+    };
+
+    let coerced = row
+        .into_iter()
+        .map(|(column, value)| {
+            let value = match column_types.get(&column) {
+                Some(sql_type) => coerce_affinity(value, sql_type),
+                None => value,
+            };
+            (column, value)
+        })
+        .collect();
+
+    Value::Object(coerced)
+}
+
+/// Coerce a raw value to the number/string/null shape implied by a SQLite
+/// column's declared type affinity (INTEGER/REAL -> number, TEXT -> string,
+/// NULL preserved).
+fn coerce_affinity(value: Value, sql_type: &str) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+
+    let affinity = sql_type.to_uppercase();
+    if affinity.contains("INT") {
+        return value
+            .as_str()
+            .and_then(|s| s.parse::<i64>().ok())
+            .map(|n| json!(n))
+            .unwrap_or(value);
+    }
+
+    if affinity.contains("REAL") || affinity.contains("FLOA") || affinity.contains("DOUB") {
+        return value
+            .as_str()
+            .and_then(|s| s.parse::<f64>().ok())
+            .map(|n| json!(n))
+            .unwrap_or(value);
+    }
+
+    if affinity.contains("CHAR") || affinity.contains("TEXT") || affinity.contains("CLOB") {
+        return match value {
+            Value::String(_) => value,
+            other => json!(other.to_string()),
+        };
+    }
+
+    value
+}
@@ -0,0 +1,113 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::response::sse::{Event, Sse};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::stream::Stream;
+use mcp_rust_sdk::error::Error;
+use mcp_rust_sdk::server::ServerHandler;
+use mcp_rust_sdk::types::{ClientCapabilities, Implementation};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
+use crate::SqlHandler;
+
+#[derive(Clone)]
+struct AppState {
+    handler: Arc<SqlHandler>,
+    notifications: broadcast::Sender<Value>,
+}
+
+/// Serve MCP over HTTP: JSON-RPC requests are POSTed to `/rpc` and answered
+/// with a single JSON response, while `/events` streams subscription
+/// notifications to any number of connected clients over SSE. This mirrors
+/// the request shape CozoDB serves at `/text-query` (JSON body in, JSON/SSE
+/// out), letting several remote clients share one torii-mcp instance.
+pub async fn serve(
+    addr: SocketAddr,
+    handler: Arc<SqlHandler>,
+    notifications: broadcast::Sender<Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let state = AppState {
+        handler,
+        notifications,
+    };
+
+    let app = Router::new()
+        .route("/rpc", post(handle_rpc))
+        .route("/events", get(handle_events))
+        .with_state(state);
+
+    println!("Listening for MCP requests on http://{}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+/// JSON-RPC 2.0 reserves -32768..-32000 for predefined errors; -32000 ("Server
+/// error") is the catch-all for application-defined failures, which is all
+/// `handle_method` ever returns.
+const JSONRPC_SERVER_ERROR: i64 = -32000;
+
+async fn handle_rpc(State(state): State<AppState>, Json(body): Json<Value>) -> impl IntoResponse {
+    let id = body.get("id").cloned().unwrap_or(Value::Null);
+    let method = body["method"].as_str().unwrap_or_default().to_string();
+    let params = body.get("params").cloned();
+
+    let result = dispatch(&state.handler, &method, params).await;
+
+    match result {
+        Ok(value) => Json(json!({ "jsonrpc": "2.0", "id": id, "result": value })),
+        Err(err) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": JSONRPC_SERVER_ERROR, "message": err.to_string() }
+        })),
+    }
+}
+
+/// Route a JSON-RPC method to the handler. `initialize`, `ping`, and
+/// `notifications/initialized` are `ServerHandler` methods (or no-ops) that
+/// the stdio `Server` loop dispatches specially before a request ever
+/// reaches `handle_method` — the HTTP transport has to do the same, or a
+/// real MCP client's handshake fails before it gets to `tools/list`.
+async fn dispatch(handler: &SqlHandler, method: &str, params: Option<Value>) -> Result<Value, Error> {
+    match method {
+        "initialize" => {
+            let implementation = match params.as_ref().and_then(|p| p.get("clientInfo")).cloned() {
+                Some(client_info) => serde_json::from_value::<Implementation>(client_info)
+                    .map_err(|e| Error::Other(format!("Invalid clientInfo: {}", e)))?,
+                None => Implementation {
+                    name: "unknown".to_string(),
+                    version: "0".to_string(),
+                },
+            };
+            let capabilities = serde_json::from_value::<ClientCapabilities>(
+                params.as_ref().and_then(|p| p.get("capabilities")).cloned().unwrap_or(json!({})),
+            )
+            .map_err(|e| Error::Other(format!("Invalid capabilities: {}", e)))?;
+
+            let server_capabilities = handler.initialize(implementation, capabilities).await?;
+            serde_json::to_value(server_capabilities).map_err(|e| Error::Other(e.to_string()))
+        }
+        "notifications/initialized" => Ok(Value::Null),
+        "ping" => Ok(json!({})),
+        _ => handler.handle_method(method, params).await,
+    }
+}
+
+async fn handle_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let stream = BroadcastStream::new(state.notifications.subscribe())
+        .filter_map(|msg| msg.ok())
+        .map(|msg| Ok(Event::default().json_data(msg).unwrap_or_else(|_| Event::default())));
+
+    Sse::new(stream)
+}
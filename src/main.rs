@@ -11,10 +11,142 @@ use mcp_rust_sdk::{
     types::{ClientCapabilities, Implementation, ServerCapabilities},
 };
 
+mod domain;
+mod format;
+mod http_transport;
+mod sql;
+mod subscribe;
+
+use format::OutputFormat;
+use sql::{bind_params, ensure_read_only};
+use subscribe::Subscriptions;
+
 static CLIENT: Lazy<Client> = Lazy::new(|| Client::new());
 
-struct SqlHandler {
+pub(crate) struct SqlHandler {
     sql_url: String,
+    allow_writes: bool,
+    pub(crate) sender: tokio::sync::broadcast::Sender<Value>,
+    subscriptions: Subscriptions,
+    /// Points back at the `Arc<SqlHandler>` this instance is wrapped in, so
+    /// spawned subscription tasks can outlive the `&self` borrow of the
+    /// `tools/call` dispatch that started them and still call back into
+    /// `run_query`. Set once at construction via `Arc::new_cyclic`.
+    self_ref: std::sync::Weak<SqlHandler>,
+}
+
+impl SqlHandler {
+    /// Run a (possibly `$name`-parameterized) query against `sql_url` and
+    /// return the decoded rows. Shared by the `schema` and `subscribe` tools
+    /// and by the higher-level domain tools (`entities`, `models`, `tokens`),
+    /// which compile down to parameterized SQL over the same pipeline. The
+    /// `query` tool runs the same bind/check/POST steps inline instead.
+    pub(crate) async fn run_query(
+        &self,
+        query: &str,
+        params: Option<&serde_json::Map<String, Value>>,
+    ) -> Result<Vec<Value>, Error> {
+        let bound_query = bind_params(query, params);
+        if !self.allow_writes {
+            ensure_read_only(&bound_query)?;
+        }
+
+        CLIENT
+            .post(&self.sql_url)
+            .json(&json!({ "query": bound_query }))
+            .send()
+            .await
+            .map_err(|e| Error::Other(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| Error::Other(e.to_string()))?
+            .json::<Vec<Value>>()
+            .await
+            .map_err(|e| Error::Other(e.to_string()))
+    }
+
+    /// Fetch column metadata for `table_filter` (or every table when `None`)
+    /// from `sqlite_master`/`pragma_table_info`, keyed by table name. Shared
+    /// by the `schema` tool and by the `query` tool's `objects`/`columns`
+    /// formatting, which needs column types to coerce SQLite affinities.
+    pub(crate) async fn fetch_schema(&self, table_filter: Option<&str>) -> Result<serde_json::Map<String, Value>, Error> {
+        let schema_query = match table_filter {
+            Some(_) => "SELECT m.name as table_name, p.*
+                 FROM sqlite_master m
+                 JOIN pragma_table_info(m.name) p
+                 WHERE m.type = 'table' AND m.name = $table
+                 ORDER BY m.name, p.cid",
+            None => "SELECT m.name as table_name, p.*
+                 FROM sqlite_master m
+                 JOIN pragma_table_info(m.name) p
+                 WHERE m.type = 'table'
+                 ORDER BY m.name, p.cid",
+        };
+        let schema_params = table_filter.map(|table| {
+            let mut map = serde_json::Map::new();
+            map.insert("table".to_string(), json!(table));
+            map
+        });
+        let response = self.run_query(schema_query, schema_params.as_ref()).await?;
+
+        let mut schema = serde_json::Map::new();
+        for row in response {
+            let row = row.as_object().unwrap();
+            let table_name = row["table_name"].as_str().unwrap();
+            let column_name = row["name"].as_str().unwrap();
+
+            let table_entry = schema.entry(table_name.to_string()).or_insert_with(|| {
+                json!({
+                    "columns": serde_json::Map::new()
+                })
+            });
+
+            if let Some(columns) = table_entry.get_mut("columns").and_then(|v| v.as_object_mut()) {
+                columns.insert(
+                    column_name.to_string(),
+                    json!({
+                        "type": row["type"],
+                        "nullable": row["notnull"] == 0,
+                        "primary_key": row["pk"] == 1,
+                        "default": row["dflt_value"]
+                    }),
+                );
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Flatten [`SqlHandler::fetch_schema`]'s per-table column map into a
+    /// single column-name -> declared-type lookup, for use by the `query`
+    /// tool's affinity coercion. Query result sets aren't scoped to one
+    /// table, so this matches by column name across the whole schema — but
+    /// a name that means different affinities in different tables (e.g. an
+    /// `id` that's TEXT in one table and INTEGER in another) can't be
+    /// resolved that way without knowing which table a given result row came
+    /// from, so such names are left out entirely and pass through
+    /// uncoerced rather than risk mis-typing them against the wrong table.
+    fn column_types(schema: &serde_json::Map<String, Value>) -> std::collections::HashMap<String, String> {
+        let mut types: std::collections::HashMap<String, Option<String>> = std::collections::HashMap::new();
+        for table in schema.values() {
+            let Some(columns) = table.get("columns").and_then(|c| c.as_object()) else {
+                continue;
+            };
+            for (column, meta) in columns {
+                let Some(sql_type) = meta.get("type").and_then(|t| t.as_str()) else {
+                    continue;
+                };
+                types
+                    .entry(column.clone())
+                    .and_modify(|existing| {
+                        if existing.as_deref() != Some(sql_type) {
+                            *existing = None; // ambiguous across tables
+                        }
+                    })
+                    .or_insert_with(|| Some(sql_type.to_string()));
+            }
+        }
+        types.into_iter().filter_map(|(column, ty)| ty.map(|ty| (column, ty))).collect()
+    }
 }
 
 #[async_trait]
@@ -47,7 +179,16 @@ impl ServerHandler for SqlHandler {
                             "properties": {
                                 "query": {
                                     "type": "string",
-                                    "description": "SQL query to execute"
+                                    "description": "SQL query to execute. Use $name placeholders and supply their values via `params`."
+                                },
+                                "params": {
+                                    "type": "object",
+                                    "description": "Optional map of $name -> value bound into the query before it is sent"
+                                },
+                                "format": {
+                                    "type": "string",
+                                    "enum": ["raw", "objects", "columns"],
+                                    "description": "Shape of the result: raw passthrough, one object per row, or a columnar layout. Defaults to raw."
                                 }
                             },
                             "required": ["query"]
@@ -65,10 +206,86 @@ impl ServerHandler for SqlHandler {
                                 }
                             }
                         }
+                    },
+                    {
+                        "name": "subscribe",
+                        "description": "Poll a query on an interval and stream newly-matching rows as notifications",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "query": {
+                                    "type": "string",
+                                    "description": "SQL query to poll"
+                                },
+                                "interval_ms": {
+                                    "type": "number",
+                                    "description": "How often to re-run the query, in milliseconds"
+                                },
+                                "cursor_column": {
+                                    "type": "string",
+                                    "description": "Monotonic column (e.g. event_id or updated_at) used to detect new rows between polls"
+                                }
+                            },
+                            "required": ["query", "interval_ms", "cursor_column"]
+                        }
+                    },
+                    {
+                        "name": "entities",
+                        "description": "List Dojo entities, optionally filtered by model name and key, without needing to know Torii's entities/entity_model join structure",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "model": {
+                                    "type": "string",
+                                    "description": "Only return entities carrying this model (e.g. \"Position\")"
+                                },
+                                "key": {
+                                    "type": "string",
+                                    "description": "Only return entities whose keys contain this substring"
+                                },
+                                "limit": {
+                                    "type": "number",
+                                    "description": "Max rows to return (default 100)"
+                                },
+                                "offset": {
+                                    "type": "number",
+                                    "description": "Rows to skip for pagination (default 0)"
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "name": "models",
+                        "description": "List registered Dojo models and their member schemas",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {}
+                        }
+                    },
+                    {
+                        "name": "tokens",
+                        "description": "Query the indexed token table, optionally filtered by contract address",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "contract_address": {
+                                    "type": "string",
+                                    "description": "Only return tokens from this contract"
+                                },
+                                "limit": {
+                                    "type": "number",
+                                    "description": "Max rows to return (default 100)"
+                                },
+                                "offset": {
+                                    "type": "number",
+                                    "description": "Rows to skip for pagination (default 0)"
+                                }
+                            }
+                        }
                     }
                 ]
             })),
-            
+
             "tools/call" => {
                 let params = params.ok_or_else(|| Error::Other("Missing parameters".into()))?;
                 let tool_name = params["name"].as_str().ok_or_else(|| Error::Other("Missing tool name".into()))?;
@@ -78,88 +295,119 @@ impl ServerHandler for SqlHandler {
                     "query" => {
                         let query = arguments["query"].as_str()
                             .ok_or_else(|| Error::Other("Missing query parameter".into()))?;
+                        let query_params = arguments.get("params").and_then(|v| v.as_object());
+                        let bound_query = bind_params(query, query_params);
+                        let format = OutputFormat::parse(arguments.get("format").and_then(|v| v.as_str()))?;
+
+                        if !self.allow_writes {
+                            ensure_read_only(&bound_query)?;
+                        }
 
-                        let response = CLIENT
+                        let request = CLIENT
                             .post(&self.sql_url)
-                            .json(&json!({ "query": query }))
+                            .json(&json!({ "query": bound_query }))
                             .send()
                             .await
                             .map_err(|e| Error::Other(e.to_string()))?
                             .error_for_status()
-                            .map_err(|e| Error::Other(e.to_string()))?
-                            .json::<Value>()
-                            .await
                             .map_err(|e| Error::Other(e.to_string()))?;
 
+                        let result = match format {
+                            OutputFormat::Raw => request
+                                .json::<Value>()
+                                .await
+                                .map_err(|e| Error::Other(e.to_string()))?,
+                            OutputFormat::Objects | OutputFormat::Columns => {
+                                let rows = request
+                                    .json::<Vec<Value>>()
+                                    .await
+                                    .map_err(|e| Error::Other(e.to_string()))?;
+                                let schema = self.fetch_schema(None).await?;
+                                let column_types = Self::column_types(&schema);
+                                format::reshape(rows, format, &column_types)
+                            }
+                        };
+
                         Ok(json!({
                             "content": [{
                                 "type": "text",
-                                "text": serde_json::to_string_pretty(&response).unwrap()
+                                "text": serde_json::to_string_pretty(&result).unwrap()
                             }]
                         }))
                     },
                     "schema" => {
                         let table_filter = arguments.get("table").and_then(|v| v.as_str());
-                        
-                        let schema_query = match table_filter {
-                            Some(table) => format!(
-                                "SELECT m.name as table_name, p.* 
-                                 FROM sqlite_master m
-                                 JOIN pragma_table_info(m.name) p
-                                 WHERE m.type = 'table' AND m.name = '{}'
-                                 ORDER BY m.name, p.cid", 
-                                table
-                            ),
-                            None => String::from(
-                                "SELECT m.name as table_name, p.* 
-                                 FROM sqlite_master m
-                                 JOIN pragma_table_info(m.name) p
-                                 WHERE m.type = 'table'
-                                 ORDER BY m.name, p.cid"
-                            ),
-                        };
+                        let schema = self.fetch_schema(table_filter).await?;
 
-                        let response = CLIENT
-                            .post(&self.sql_url)
-                            .json(&json!({ "query": schema_query }))
-                            .send()
-                            .await
-                            .map_err(|e| Error::Other(e.to_string()))?
-                            .error_for_status()
-                            .map_err(|e| Error::Other(e.to_string()))?
-                            .json::<Vec<Value>>()
-                            .await
-                            .map_err(|e| Error::Other(e.to_string()))?;
+                        Ok(json!({
+                            "content": [{
+                                "type": "text",
+                                "text": serde_json::to_string_pretty(&schema).unwrap()
+                            }]
+                        }))
+                    },
+                    "subscribe" => {
+                        let query = arguments["query"].as_str()
+                            .ok_or_else(|| Error::Other("Missing query parameter".into()))?;
+                        let interval_ms = arguments["interval_ms"].as_u64()
+                            .ok_or_else(|| Error::Other("Missing interval_ms parameter".into()))?;
+                        let cursor_column = arguments["cursor_column"].as_str()
+                            .ok_or_else(|| Error::Other("Missing cursor_column parameter".into()))?;
 
-                        let mut schema = serde_json::Map::new();
-                        for row in response {
-                            let row = row.as_object().unwrap();
-                            let table_name = row["table_name"].as_str().unwrap();
-                            let column_name = row["name"].as_str().unwrap();
-                            
-                            let table_entry = schema.entry(table_name.to_string()).or_insert_with(|| {
-                                json!({
-                                    "columns": serde_json::Map::new()
-                                })
-                            });
-
-                            if let Some(columns) = table_entry.get_mut("columns").and_then(|v| v.as_object_mut()) {
-                                columns.insert(
-                                    column_name.to_string(),
-                                    json!({
-                                        "type": row["type"],
-                                        "nullable": row["notnull"] == 0,
-                                        "primary_key": row["pk"] == 1,
-                                        "default": row["dflt_value"]
-                                    }),
-                                );
-                            }
+                        if !self.allow_writes {
+                            ensure_read_only(query)?;
                         }
 
+                        let id = self.subscriptions.start(
+                            self.self_ref.clone(),
+                            query.to_string(),
+                            interval_ms,
+                            cursor_column.to_string(),
+                        ).await?;
+
                         Ok(json!({
                             "content": [{
                                 "type": "text",
-                                "text": serde_json::to_string_pretty(&schema).unwrap()
+                                "text": format!("Subscribed (id {}); new rows will arrive as notifications/subscription", id)
+                            }]
+                        }))
+                    },
+                    "entities" => {
+                        let model = arguments.get("model").and_then(|v| v.as_str());
+                        let key = arguments.get("key").and_then(|v| v.as_str());
+                        let limit = arguments.get("limit").and_then(|v| v.as_i64()).unwrap_or(100);
+                        let offset = arguments.get("offset").and_then(|v| v.as_i64()).unwrap_or(0);
+
+                        let result = domain::entities(self, model, key, limit, offset).await?;
+
+                        Ok(json!({
+                            "content": [{
+                                "type": "text",
+                                "text": serde_json::to_string_pretty(&result).unwrap()
+                            }]
+                        }))
+                    },
+                    "models" => {
+                        let result = domain::models(self).await?;
+
+                        Ok(json!({
+                            "content": [{
+                                "type": "text",
+                                "text": serde_json::to_string_pretty(&result).unwrap()
+                            }]
+                        }))
+                    },
+                    "tokens" => {
+                        let contract_address = arguments.get("contract_address").and_then(|v| v.as_str());
+                        let limit = arguments.get("limit").and_then(|v| v.as_i64()).unwrap_or(100);
+                        let offset = arguments.get("offset").and_then(|v| v.as_i64()).unwrap_or(0);
+
+                        let result = domain::tokens(self, contract_address, limit, offset).await?;
+
+                        Ok(json!({
+                            "content": [{
+                                "type": "text",
+                                "text": serde_json::to_string_pretty(&result).unwrap()
                             }]
                         }))
                     },
@@ -172,6 +420,7 @@ impl ServerHandler for SqlHandler {
     }
 
     async fn shutdown(&self) -> Result<(), Error> {
+        self.subscriptions.cancel_all().await;
         println!("Server shutting down");
         Ok(())
     }
@@ -179,17 +428,68 @@ impl ServerHandler for SqlHandler {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let sql_url = std::env::args()
-        .nth(1)
-        .expect("Please provide SQL endpoint URL (e.g., http://localhost:8080/sql)");
-
-    let (transport, _sender) = StdioTransport::new();
-    let handler = SqlHandler {
-        sql_url,
-    };
-    
-    let server = Server::new(Arc::new(transport), Arc::new(handler));
-    server.start().await?;
+    let mut sql_url = None;
+    let mut allow_writes = false;
+    let mut transport_kind = "stdio".to_string();
+    let mut http_addr = "127.0.0.1:8090".to_string();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allow-writes" => allow_writes = true,
+            "--transport" => {
+                transport_kind = args
+                    .next()
+                    .expect("--transport requires a value (stdio or http)");
+            }
+            "--addr" => {
+                http_addr = args.next().expect("--addr requires a value");
+            }
+            _ => sql_url = Some(arg),
+        }
+    }
+
+    let sql_url = sql_url.expect("Please provide SQL endpoint URL (e.g., http://localhost:8080/sql)");
+    let (notifications, _) = tokio::sync::broadcast::channel(1024);
+
+    match transport_kind.as_str() {
+        "stdio" => {
+            let (transport, stdio_sender) = StdioTransport::new();
+
+            let mut forward_rx = notifications.subscribe();
+            tokio::spawn(async move {
+                while let Ok(message) = forward_rx.recv().await {
+                    if stdio_sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let handler = Arc::new_cyclic(|self_ref| SqlHandler {
+                sql_url,
+                allow_writes,
+                sender: notifications,
+                subscriptions: Subscriptions::new(),
+                self_ref: self_ref.clone(),
+            });
+
+            let server = Server::new(Arc::new(transport), handler);
+            server.start().await?;
+        }
+        "http" => {
+            let handler = Arc::new_cyclic(|self_ref| SqlHandler {
+                sql_url,
+                allow_writes,
+                sender: notifications.clone(),
+                subscriptions: Subscriptions::new(),
+                self_ref: self_ref.clone(),
+            });
+
+            let addr: std::net::SocketAddr = http_addr.parse()?;
+            http_transport::serve(addr, handler, notifications).await?;
+        }
+        other => panic!("Unknown --transport '{}', expected 'stdio' or 'http'", other),
+    }
 
     Ok(())
 }
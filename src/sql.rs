@@ -0,0 +1,172 @@
+use serde_json::Value;
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::SQLiteDialect;
+use sqlparser::parser::Parser;
+
+use mcp_rust_sdk::error::Error;
+
+/// Substitute `$name` tokens in `query` with values from `params`, quoting
+/// strings with SQLite escaping rules (wrap in single quotes, double any
+/// embedded single quote) and emitting numbers/booleans/null inline.
+///
+/// This mirrors CozoDB's HTTP API, which accepts a `script` plus a separate
+/// `params` map rather than letting callers interpolate values into the SQL
+/// text themselves.
+pub fn bind_params(query: &str, params: Option<&serde_json::Map<String, Value>>) -> String {
+    let Some(params) = params else {
+        return query.to_string();
+    };
+
+    let mut bound = String::with_capacity(query.len());
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if c != '$' {
+            bound.push(c);
+            continue;
+        }
+
+        let mut name = String::new();
+        while let Some(&(_, next)) = chars.peek() {
+            if next.is_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if name.is_empty() {
+            bound.push('$');
+            continue;
+        }
+
+        match params.get(&name) {
+            Some(value) => bound.push_str(&sql_literal(value)),
+            None => {
+                bound.push('$');
+                bound.push_str(&name);
+            }
+        }
+    }
+
+    bound
+}
+
+/// Render a JSON value as a SQLite literal for use by [`bind_params`].
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => (if *b { "1" } else { "0" }).to_string(),
+        Value::Null => "NULL".to_string(),
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
+
+/// Validate that `name` is a bare SQL identifier (`[A-Za-z_][A-Za-z0-9_]*`).
+/// Identifiers (e.g. a column name used to build a `WHERE` clause) can't be
+/// passed through [`bind_params`] like a value, since they're syntax, not
+/// data — so callers that have to interpolate one must check it against
+/// this instead of splicing it in raw.
+pub fn validate_identifier(name: &str) -> Result<(), Error> {
+    let mut chars = name.chars();
+    let starts_ok = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+    let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if starts_ok && rest_ok {
+        Ok(())
+    } else {
+        Err(Error::Other(format!("'{}' is not a valid identifier", name)))
+    }
+}
+
+/// Reject anything but a single read-only statement, mirroring the
+/// Automaat SQL-query processor's "SELECT statements only" guarantee.
+///
+/// Allows `SELECT`/`WITH ... SELECT`, which covers the `sqlite_master`/
+/// `pragma_table_info()` table-function access the `schema` tool depends on
+/// (that's a SELECT over a table-valued function, not a bare `PRAGMA`
+/// statement — sqlparser's SQLite dialect doesn't parse `PRAGMA
+/// table_info(...)` as a value, so there's no read-only PRAGMA form to allow
+/// here). Multi-statement batches and any write/DDL statement are rejected.
+pub fn ensure_read_only(query: &str) -> Result<(), Error> {
+    let statements = Parser::parse_sql(&SQLiteDialect {}, query)
+        .map_err(|e| Error::Other(format!("Failed to parse query: {}", e)))?;
+
+    if statements.len() != 1 {
+        return Err(Error::Other(
+            "Only a single read-only statement is allowed per query".into(),
+        ));
+    }
+
+    match &statements[0] {
+        Statement::Query(query) => ensure_read_only_query(query),
+        other => Err(Error::Other(format!(
+            "Statement type not permitted in read-only mode: {}",
+            other
+        ))),
+    }
+}
+
+/// Check every CTE definition of `query` as well as its body.
+fn ensure_read_only_query(query: &Query) -> Result<(), Error> {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            ensure_read_only_query(&cte.query)?;
+        }
+    }
+    ensure_read_only_body(&query.body)
+}
+
+/// Recurse into set operations (UNION/INTERSECT/EXCEPT) and CTEs to make
+/// sure a write isn't smuggled in behind a `WITH` clause.
+fn ensure_read_only_body(body: &SetExpr) -> Result<(), Error> {
+    match body {
+        SetExpr::Select(_) | SetExpr::Values(_) | SetExpr::Table(_) => Ok(()),
+        SetExpr::Query(query) => ensure_read_only_query(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            ensure_read_only_body(left)?;
+            ensure_read_only_body(right)
+        }
+        other => Err(Error::Other(format!(
+            "Query body not permitted in read-only mode: {}",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_plain_select() {
+        assert!(ensure_read_only("SELECT * FROM entities WHERE id = 1").is_ok());
+    }
+
+    #[test]
+    fn rejects_write_disguised_in_a_cte() {
+        let query = "WITH x AS (SELECT 1) DELETE FROM entities WHERE id IN (SELECT * FROM x)";
+        assert!(ensure_read_only(query).is_err());
+    }
+
+    #[test]
+    fn rejects_stacked_statements() {
+        let query = "SELECT * FROM entities; DROP TABLE entities;";
+        assert!(ensure_read_only(query).is_err());
+    }
+
+    #[test]
+    fn validates_plain_identifiers() {
+        assert!(validate_identifier("event_id").is_ok());
+        assert!(validate_identifier("_updatedAt2").is_ok());
+    }
+
+    #[test]
+    fn rejects_identifiers_with_injected_sql() {
+        assert!(validate_identifier("id; DROP TABLE entities;--").is_err());
+        assert!(validate_identifier("1id").is_err());
+        assert!(validate_identifier("").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Weak;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use mcp_rust_sdk::error::Error;
+
+use crate::sql::validate_identifier;
+use crate::SqlHandler;
+
+/// Tracks the background polling tasks spawned by the `subscribe` tool so
+/// `shutdown` can cancel them cleanly instead of leaving them running past
+/// the lifetime of the server.
+pub struct Subscriptions {
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<u64, JoinHandle<()>>>,
+}
+
+impl Subscriptions {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start polling `query` every `interval_ms` and push newly-arrived rows
+    /// (those at or past `cursor_column`'s highest value seen so far) as MCP
+    /// notifications. The first tick is a silent snapshot: it seeds the
+    /// cursor from whatever's already there without emitting a notification,
+    /// so only rows that show up *after* the subscription starts count as
+    /// "new". From then on the window is `cursor_column >= last`, not `>`,
+    /// because cursor values aren't guaranteed unique — rows already emitted
+    /// at the boundary are tracked in `seen_at_cursor` and deduped back out,
+    /// so a same-cursor row that arrives on a later tick is still caught
+    /// instead of being permanently skipped by a strict `>`.
+    ///
+    /// Every tick is re-run through [`SqlHandler::run_query`] (via
+    /// `handler`) so it gets the same read-only enforcement and parameter
+    /// binding as any other query, not just the one checked when the
+    /// subscription was created. Returns the new subscription's id.
+    pub async fn start(
+        &self,
+        handler: Weak<SqlHandler>,
+        query: String,
+        interval_ms: u64,
+        cursor_column: String,
+    ) -> Result<u64, Error> {
+        validate_identifier(&cursor_column)?;
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        let handle = tokio::spawn(async move {
+            let mut last_cursor: Option<Value> = None;
+            let mut seen_at_cursor: HashSet<String> = HashSet::new();
+            let mut seeded = false;
+
+            loop {
+                let Some(handler) = handler.upgrade() else {
+                    break;
+                };
+                let is_first_tick = !seeded;
+
+                let result = match &last_cursor {
+                    Some(last) => {
+                        let mut params = serde_json::Map::new();
+                        params.insert("last".to_string(), last.clone());
+                        let windowed_query = format!(
+                            "SELECT * FROM ({}) AS subscribe_window WHERE {} >= $last",
+                            query, cursor_column
+                        );
+                        handler.run_query(&windowed_query, Some(&params)).await
+                    }
+                    None => handler.run_query(&query, None).await,
+                };
+
+                if let Ok(rows) = result {
+                    seeded = true;
+                    let new_max = max_cursor(&rows, &cursor_column);
+
+                    let fresh_rows: Vec<Value> = if is_first_tick {
+                        Vec::new()
+                    } else {
+                        rows.iter()
+                            .filter(|row| {
+                                let at_old_boundary = row.get(&cursor_column) == last_cursor.as_ref();
+                                !at_old_boundary || !seen_at_cursor.contains(&row_fingerprint(row))
+                            })
+                            .cloned()
+                            .collect()
+                    };
+
+                    if let Some(max) = &new_max {
+                        if Some(max) != last_cursor.as_ref() {
+                            seen_at_cursor.clear();
+                        }
+                        for row in &rows {
+                            if row.get(&cursor_column) == Some(max) {
+                                seen_at_cursor.insert(row_fingerprint(row));
+                            }
+                        }
+                        last_cursor = new_max;
+                    }
+
+                    if !fresh_rows.is_empty() {
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/subscription",
+                            "params": {
+                                "subscriptionId": id,
+                                "rows": fresh_rows,
+                            }
+                        });
+
+                        // An error here just means nobody is currently
+                        // listening; the subscription stays alive so a
+                        // client reconnecting later still gets fresh rows.
+                        let _ = handler.sender.send(notification);
+                    }
+                }
+
+                tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            }
+        });
+
+        self.tasks.lock().await.insert(id, handle);
+        Ok(id)
+    }
+
+    /// Abort every active subscription task. Called from `shutdown`.
+    pub async fn cancel_all(&self) {
+        let mut tasks = self.tasks.lock().await;
+        for (_, handle) in tasks.drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// Find the largest value of `cursor_column` across `rows`, comparing
+/// numerically when possible and falling back to string comparison.
+fn max_cursor(rows: &[Value], cursor_column: &str) -> Option<Value> {
+    rows.iter()
+        .filter_map(|row| row.get(cursor_column))
+        .cloned()
+        .max_by(|a, b| match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+            _ => a.to_string().cmp(&b.to_string()),
+        })
+}
+
+/// A stable identity for a row, used to tell "already emitted" rows at the
+/// cursor boundary apart from genuinely new ones sharing the same cursor
+/// value. `serde_json::Value`'s object map is key-sorted, so this is
+/// deterministic across ticks for the same row.
+fn row_fingerprint(row: &Value) -> String {
+    serde_json::to_string(row).unwrap_or_default()
+}